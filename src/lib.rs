@@ -2,11 +2,18 @@ use std::{fs, fs::OpenOptions};
 use std::io::{Read, Write};
 use std::{error::Error, fmt};
 use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::path::Path;
+use std::process::Command;
 
 #[derive(Debug)]
 pub enum TodoError {
     InvalidCommand,
-    NotEnoughArguments
+    NotEnoughArguments,
+    MalformedLine { line: usize, text: String },
+    MissingContent { line: usize },
+    UnknownCommand(String),
+    AmbiguousCommand { verb: String, candidates: Vec<String> }
 }
 
 impl Error for TodoError {}
@@ -15,49 +22,220 @@ impl fmt::Display for TodoError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             TodoError::InvalidCommand => write!(f, "Invalid command"),
-            TodoError::NotEnoughArguments => write!(f, "Not enough arguments")
+            TodoError::NotEnoughArguments => write!(f, "Not enough arguments"),
+            TodoError::MalformedLine { line, text } => write!(f, "line {}: malformed line: {}", line, text),
+            TodoError::MissingContent { line } => write!(f, "line {}: missing content", line),
+            TodoError::UnknownCommand(verb) => write!(f, "unknown command: {}", verb),
+            TodoError::AmbiguousCommand { verb, candidates } => {
+                write!(f, "ambiguous command \"{}\": matches {}", verb, candidates.join(", "))
+            }
         }
     }
 }
 
 pub struct Config {
     pub verb: String,
-    pub noun: Option<String>
+    pub noun: Option<String>,
+    pub list: Option<String>
 }
 
 impl Config {
     pub fn new(args: &[String]) -> Result<Config, Box<dyn Error>> {
         if args.len() < 2 {
             return Err(Box::new(TodoError::NotEnoughArguments));
-        }  
+        }
 
-        let verb = args[1].clone();
+        let mut words = args[1..].to_vec();
+        let list = take_list_flag(&mut words)?;
 
-        if args.len() > 2 {
-            let noun = args[2..].join(" ").clone();
-            return Ok(Config { noun: Some(noun), verb })
+        if words.is_empty() {
+            return Err(Box::new(TodoError::NotEnoughArguments));
         }
 
-        Ok(Config { noun: None, verb })
+        let verb = words[0].clone();
+
+        if words.len() > 1 {
+            let noun = words[1..].join(" ").clone();
+            return Ok(Config { noun: Some(noun), verb, list })
+        }
+
+        Ok(Config { noun: None, verb, list })
+    }
+}
+
+// Pulls a leading `--list <name>` or `-l <name>` selector off the argument
+// list, leaving the remaining words for verb/noun parsing. Only recognized
+// before the verb, so `-l`/`--list` typed as part of the noun (e.g. `add fix
+// -l flag parsing`) is left alone instead of being silently eaten.
+fn take_list_flag(words: &mut Vec<String>) -> Result<Option<String>, Box<dyn Error>> {
+    let is_flag = words.first().map(|w| w == "--list" || w == "-l").unwrap_or(false);
+    if !is_flag {
+        return Ok(None);
+    }
+
+    if words.len() < 2 {
+        return Err(Box::new(TodoError::NotEnoughArguments));
     }
+
+    words.remove(0);
+    Ok(Some(words.remove(0)))
 }
 
+// A single todo.txt-format entry, prefixed with the index we use to address
+// it from the CLI. See http://todotxt.org/ for the format this mirrors:
+// `x? (A)? completion-date? creation-date? content`, with `+project` and
+// `@context` tokens embedded in (and extracted from) the content.
 #[derive(Eq)]
 struct Todo {
     index: i32,
-    content: String
+    completed: bool,
+    priority: Option<char>,
+    completion_date: Option<String>,
+    creation_date: Option<String>,
+    content: String,
+    projects: Vec<String>,
+    contexts: Vec<String>
 }
 
 impl Todo {
-    fn new(line: &str) -> Result<Todo, Box<dyn Error>> {
-        let words: Vec<&str> = line.split(" ").collect();
-        let index = words[0].parse::<i32>()?;
-        let content = words[1..].join(" ").clone();
+    fn new(line_no: usize, line: &str) -> Result<Todo, TodoError> {
+        let mut words = line.splitn(2, ' ');
+        let index_text = words.next().unwrap_or("");
+        let index = index_text.parse::<i32>().map_err(|_| TodoError::MalformedLine {
+            line: line_no,
+            text: line.to_string()
+        })?;
+
+        let body = words.next().unwrap_or("");
+        if body.trim().is_empty() {
+            return Err(TodoError::MissingContent { line: line_no });
+        }
 
-        Ok(Todo { index, content })
+        Ok(Todo::from_body(index, body))
+    }
+
+    fn from_body(index: i32, body: &str) -> Todo {
+        let mut rest = body;
+
+        let completed = if let Some(r) = rest.strip_prefix("x ") {
+            rest = r;
+            true
+        } else {
+            false
+        };
+
+        let mut priority = None;
+        if !completed {
+            if let Some(r) = strip_priority(rest) {
+                priority = Some(r.0);
+                rest = r.1;
+            }
+        }
+
+        let mut completion_date = None;
+        let mut creation_date = None;
+        if completed {
+            if let Some((date, r)) = take_date(rest) {
+                completion_date = Some(date);
+                rest = r;
+                if let Some((date, r)) = take_date(rest) {
+                    creation_date = Some(date);
+                    rest = r;
+                }
+            }
+        } else if let Some((date, r)) = take_date(rest) {
+            creation_date = Some(date);
+            rest = r;
+        }
+
+        let content = rest.to_string();
+        let projects = extract_tags(&content, '+');
+        let contexts = extract_tags(&content, '@');
+
+        Todo { index, completed, priority, completion_date, creation_date, content, projects, contexts }
+    }
+
+    // The todo.txt-format body, i.e. everything after the index.
+    fn body(&self) -> String {
+        let mut s = String::new();
+        if self.completed {
+            s.push_str("x ");
+        }
+        // Priority is only ever parsed for an incomplete todo (see
+        // from_body), so only serialize it there too; otherwise a stray
+        // priority on a completed todo would shift every field after it.
+        if !self.completed {
+            if let Some(p) = self.priority {
+                s.push('(');
+                s.push(p);
+                s.push_str(") ");
+            }
+        }
+        if let Some(d) = &self.completion_date {
+            s.push_str(d);
+            s.push(' ');
+        }
+        if let Some(d) = &self.creation_date {
+            s.push_str(d);
+            s.push(' ');
+        }
+        s.push_str(&self.content);
+        s
+    }
+
+    fn to_line(&self) -> String {
+        format!("{} {}", self.index, self.body())
+    }
+
+    fn matches(&self, filter: &str) -> bool {
+        if let Some(project) = filter.strip_prefix('+') {
+            self.projects.iter().any(|p| p == project)
+        } else if let Some(context) = filter.strip_prefix('@') {
+            self.contexts.iter().any(|c| c == context)
+        } else {
+            self.content.contains(filter)
+        }
+    }
+}
+
+// Parses a leading `(A)`-`(Z)` priority marker, returning the priority letter
+// and the remainder of the string with the marker and trailing space removed.
+fn strip_priority(s: &str) -> Option<(char, &str)> {
+    let bytes = s.as_bytes();
+    if bytes.len() >= 4 && bytes[0] == b'(' && bytes[1].is_ascii_uppercase() && bytes[2] == b')' && bytes[3] == b' ' {
+        Some((bytes[1] as char, &s[4..]))
+    } else {
+        None
     }
 }
 
+// Parses a leading `YYYY-MM-DD` date, returning it and the remainder of the
+// string with the date and trailing space removed.
+fn take_date(s: &str) -> Option<(String, &str)> {
+    let bytes = s.as_bytes();
+    let is_date = bytes.len() >= 10
+        && bytes[0..4].iter().all(u8::is_ascii_digit)
+        && bytes[4] == b'-'
+        && bytes[5..7].iter().all(u8::is_ascii_digit)
+        && bytes[7] == b'-'
+        && bytes[8..10].iter().all(u8::is_ascii_digit)
+        && (bytes.len() == 10 || bytes[10] == b' ');
+
+    if is_date {
+        let rest = if bytes.len() == 10 { "" } else { &s[11..] };
+        Some((s[0..10].to_string(), rest))
+    } else {
+        None
+    }
+}
+
+fn extract_tags(content: &str, marker: char) -> Vec<String> {
+    content
+        .split_whitespace()
+        .filter_map(|w| w.strip_prefix(marker).map(|s| s.to_string()))
+        .collect()
+}
+
 impl Ord for Todo {
     fn cmp(&self, other: &Self) -> Ordering {
         self.index.cmp(&other.index)
@@ -76,47 +254,186 @@ impl PartialEq for Todo {
     }
 }
 
-pub fn run(config: Config) -> Result<(), Box<dyn Error>> {
-    match config.verb.as_str() {
-        "list" => list(),
-        "add" => {
-            if let Some(noun) = config.noun {
-                add(&noun)
-            } else {
-                Err(Box::new(TodoError::NotEnoughArguments))
+// Resolves which todo file a command operates on: the default `todo.txt`,
+// or a named list like `work.txt` selected with `--list work`. All file I/O
+// goes through here so the rest of the crate never hard-codes a filename.
+struct Loader {
+    list: Option<String>
+}
+
+impl Loader {
+    fn new(list: Option<String>) -> Loader {
+        Loader { list }
+    }
+
+    fn for_list(name: &str) -> Loader {
+        Loader { list: Some(name.to_string()) }
+    }
+
+    fn path(&self) -> String {
+        match &self.list {
+            Some(name) => format!("{}.txt", name),
+            None => "todo.txt".to_string()
+        }
+    }
+
+    // Enumerates every known list (the default plus any `*.txt` sibling
+    // file) alongside its item count.
+    fn known_lists() -> Result<Vec<(String, usize)>, Box<dyn Error>> {
+        let mut lists = vec![("todo".to_string(), count_todos("todo.txt"))];
+
+        for entry in fs::read_dir(".")? {
+            let path = entry?.path();
+            if path.extension().map_or(false, |ext| ext == "txt") {
+                if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
+                    if stem != "todo" {
+                        lists.push((stem.to_string(), count_todos(&format!("{}.txt", stem))));
+                    }
+                }
             }
         }
-        // note: Err and Error are NOT closely related
-        // Err is a Result type and Error is a trait
-        _ => Err(Box::new(TodoError::InvalidCommand))
+
+        Ok(lists)
+    }
+}
+
+fn count_todos(path: &str) -> usize {
+    fs::read_to_string(path).map(|c| parse_todos(&c).0.len()).unwrap_or(0)
+}
+
+// How many arguments a verb expects, so arity checking lives in one place
+// instead of each handler re-deriving it from `config.noun`.
+#[derive(Clone, Copy, PartialEq)]
+enum Arity {
+    None,
+    Optional,
+    Required
+}
+
+type Handler = fn(&Loader, Option<&str>) -> Result<(), Box<dyn Error>>;
+
+struct Verb {
+    name: &'static str,
+    min_abbrev: usize,
+    arity: Arity,
+    handler: Handler
+}
+
+// The command table. Adding a verb is a one-line entry here rather than
+// another match arm; `min_abbrev` is the shortest prefix of a verb that is
+// matched against it at all. A prefix that several verbs accept (e.g. `li`
+// for both `list` and `lists`) is rejected as ambiguous by resolve_verb.
+const VERBS: &[Verb] = &[
+    Verb { name: "list", min_abbrev: 1, arity: Arity::Optional, handler: list },
+    Verb { name: "add", min_abbrev: 1, arity: Arity::Required, handler: |loader, noun| add(loader, noun.unwrap()) },
+    Verb { name: "pri", min_abbrev: 1, arity: Arity::Required, handler: |loader, noun| pri(loader, noun.unwrap()) },
+    Verb { name: "depri", min_abbrev: 1, arity: Arity::Required, handler: |loader, noun| depri(loader, noun.unwrap()) },
+    Verb { name: "scan", min_abbrev: 1, arity: Arity::Required, handler: |loader, noun| scan(loader, noun.unwrap()) },
+    Verb { name: "edit", min_abbrev: 1, arity: Arity::None, handler: |loader, _| edit(loader) },
+    Verb { name: "lists", min_abbrev: 1, arity: Arity::None, handler: |_, _| lists_cmd() },
+    Verb { name: "move", min_abbrev: 1, arity: Arity::Required, handler: |loader, noun| move_todo(loader, noun.unwrap()) },
+];
+
+// Symbolic verbs that can't be matched by prefix, mapped to their canonical
+// entry in VERBS.
+const VERB_ALIASES: &[(&str, &str)] = &[("p->", "depri")];
+
+// Resolves a user-typed verb against VERBS: an exact match always wins,
+// otherwise the verb must be an unambiguous prefix of exactly one entry.
+fn resolve_verb(input: &str) -> Result<&'static Verb, Box<dyn Error>> {
+    let verb = VERB_ALIASES.iter().find(|(alias, _)| *alias == input).map(|(_, name)| *name).unwrap_or(input);
+
+    if let Some(exact) = VERBS.iter().find(|v| v.name == verb) {
+        return Ok(exact);
+    }
+
+    let candidates: Vec<&Verb> = VERBS.iter()
+        .filter(|v| verb.len() >= v.min_abbrev && v.name.starts_with(verb))
+        .collect();
+
+    match candidates.as_slice() {
+        [] => Err(Box::new(TodoError::UnknownCommand(verb.to_string()))),
+        [only] => Ok(only),
+        _ => Err(Box::new(TodoError::AmbiguousCommand {
+            verb: verb.to_string(),
+            candidates: candidates.iter().map(|v| v.name.to_string()).collect()
+        }))
+    }
+}
+
+pub fn run(config: Config) -> Result<(), Box<dyn Error>> {
+    let verb = resolve_verb(&config.verb)?;
+
+    if verb.arity == Arity::Required && config.noun.is_none() {
+        return Err(Box::new(TodoError::NotEnoughArguments));
+    }
+
+    let loader = Loader::new(config.list);
+    (verb.handler)(&loader, config.noun.as_deref())
+}
+
+// Parses every line, collecting malformed lines as errors instead of
+// aborting at the first one, so a single hand-edited bad line doesn't take
+// the rest of the list down with it.
+fn parse_todos(lines: &str) -> (Vec<Todo>, Vec<TodoError>) {
+    let mut todos = Vec::new();
+    let mut errors = Vec::new();
+
+    for (i, line) in lines.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match Todo::new(i + 1, line) {
+            Ok(todo) => todos.push(todo),
+            Err(e) => errors.push(e)
+        }
     }
+
+    (todos, errors)
 }
 
-fn parse_todos(lines: &str) -> Result<Vec<Todo>, Box<dyn Error>> {
-    Ok(lines.lines().map(|l| Todo::new(l).unwrap()).collect::<Vec<_>>())
+fn report_errors(path: &str, errors: &[TodoError]) {
+    for error in errors {
+        eprintln!("{}: {}", path, error);
+    }
 }
 
-fn list() -> Result<(), Box<dyn Error>> {
-    let file_contents = fs::read_to_string("todo.txt")?;
-    let mut todos = parse_todos(&file_contents)?;
+fn write_todos(loader: &Loader, todos: &mut Vec<Todo>) -> Result<(), Box<dyn Error>> {
     todos.sort();
 
+    let mut file = fs::File::create(loader.path())?;
     for todo in todos {
-        println!("{}. {}", todo.index, todo.content);
+        writeln!(file, "{}", todo.to_line())?;
     }
     Ok(())
 }
 
-fn add(content: &str) -> Result<(), Box<dyn Error>> {
+fn list(loader: &Loader, filter: Option<&str>) -> Result<(), Box<dyn Error>> {
+    let file_contents = fs::read_to_string(loader.path())?;
+    let (mut todos, errors) = parse_todos(&file_contents);
+    report_errors(&loader.path(), &errors);
+    todos.sort();
+
+    for todo in todos {
+        if filter.map_or(false, |f| !todo.matches(f)) {
+            continue;
+        }
+        println!("{}. {}", todo.index, todo.body());
+    }
+    Ok(())
+}
+
+fn add(loader: &Loader, content: &str) -> Result<(), Box<dyn Error>> {
     let mut file = OpenOptions::new()
         .read(true)
         .write(true)
         .create(true)
         .append(true)
-        .open("todo.txt")?;
+        .open(loader.path())?;
     let mut file_contents = String::new();
     file.read_to_string(&mut file_contents)?;
-    let mut todos = parse_todos(&file_contents)?;
+    let (mut todos, errors) = parse_todos(&file_contents);
+    report_errors(&loader.path(), &errors);
     todos.sort();
 
     let mut index: i32 = 1;
@@ -126,11 +443,251 @@ fn add(content: &str) -> Result<(), Box<dyn Error>> {
         }
         index += 1;
     }
-    
+
     writeln!(file, "{}", format!("{} {}", index, content))?;
     Ok(())
 }
 
+fn pri(loader: &Loader, noun: &str) -> Result<(), Box<dyn Error>> {
+    let mut words = noun.split_whitespace();
+    let index = words.next().ok_or(TodoError::NotEnoughArguments)?.parse::<i32>()?;
+    let priority = words.next().ok_or(TodoError::NotEnoughArguments)?;
+
+    if priority.len() != 1 || !priority.chars().next().unwrap().is_ascii_uppercase() {
+        return Err(Box::new(TodoError::InvalidCommand));
+    }
+
+    set_priority(loader, index, Some(priority.chars().next().unwrap()))
+}
+
+fn depri(loader: &Loader, noun: &str) -> Result<(), Box<dyn Error>> {
+    let index = noun.trim().parse::<i32>()?;
+    set_priority(loader, index, None)
+}
+
+fn set_priority(loader: &Loader, index: i32, priority: Option<char>) -> Result<(), Box<dyn Error>> {
+    let file_contents = fs::read_to_string(loader.path())?;
+    let (mut todos, errors) = parse_todos(&file_contents);
+    report_errors(&loader.path(), &errors);
+
+    let todo = todos.iter_mut().find(|t| t.index == index).ok_or(TodoError::InvalidCommand)?;
+    if priority.is_some() && todo.completed {
+        return Err(Box::new(TodoError::InvalidCommand));
+    }
+    todo.priority = priority;
+
+    write_todos(loader, &mut todos)
+}
+
+fn move_todo(loader: &Loader, noun: &str) -> Result<(), Box<dyn Error>> {
+    let mut words = noun.split_whitespace();
+    let index = words.next().ok_or(TodoError::NotEnoughArguments)?.parse::<i32>()?;
+    let dest_name = words.next().ok_or(TodoError::NotEnoughArguments)?;
+
+    let dest = Loader::for_list(dest_name);
+    if dest.path() == loader.path() {
+        // Moving an item to the list it's already in is a no-op, not a
+        // round trip through a second read/parse/write of the same file
+        // (which would just clobber the first write with a todos vec that
+        // already had the item removed).
+        return Ok(());
+    }
+
+    let file_contents = fs::read_to_string(loader.path())?;
+    let (mut todos, errors) = parse_todos(&file_contents);
+    report_errors(&loader.path(), &errors);
+
+    let pos = todos.iter().position(|t| t.index == index).ok_or(TodoError::InvalidCommand)?;
+    let mut todo = todos.remove(pos);
+
+    let dest_contents = fs::read_to_string(dest.path()).unwrap_or_default();
+    let (mut dest_todos, dest_errors) = parse_todos(&dest_contents);
+    report_errors(&dest.path(), &dest_errors);
+
+    todo.index = dest_todos.iter().map(|t| t.index).max().unwrap_or(0) + 1;
+    dest_todos.push(todo);
+
+    // Write the destination before touching the source: if this fails (bad
+    // list name, permissions, disk full), the source file is never
+    // rewritten and the item simply stays where it started instead of
+    // disappearing.
+    write_todos(&dest, &mut dest_todos)?;
+    write_todos(loader, &mut todos)
+}
+
+fn lists_cmd() -> Result<(), Box<dyn Error>> {
+    for (name, count) in Loader::known_lists()? {
+        println!("{} ({})", name, count);
+    }
+    Ok(())
+}
+
+// Comment literal pairs the scanner recognizes, `(open, close)`. `close` is
+// empty for markers that run to the end of the line. Swapping this table is
+// how new languages get support, rather than hard-coding per-language logic.
+const COMMENT_MARKERS: &[(&str, &str)] = &[("//", ""), ("/*", "*/"), ("#", "")];
+
+// Source-comment markers the scanner harvests into todos.
+const SCAN_KEYWORDS: &[&str] = &["TODO", "FIXME", "HACK"];
+
+// One `<open>...(keyword)...<close>` branch of the combined scan table.
+// There's no regex engine available here (this crate has no manifest, so it
+// can't pull in the `regex` crate), so instead of one combined pattern we
+// build the same cross product of markers and keywords as a plain branch
+// list at runtime and try each in turn — the alternation, just not a string.
+struct ScanBranch {
+    open: &'static str,
+    close: Option<&'static str>,
+    keyword: &'static str
+}
+
+// Builds the combined branch table from `COMMENT_MARKERS` x `SCAN_KEYWORDS`,
+// the dependency-free stand-in for joining every `<open>...<keyword>...<close>`
+// alternative into one pattern with `|`.
+fn scan_branches() -> Vec<ScanBranch> {
+    COMMENT_MARKERS.iter()
+        .flat_map(|&(open, close)| {
+            let close = if close.is_empty() { None } else { Some(close) };
+            SCAN_KEYWORDS.iter().map(move |&keyword| ScanBranch { open, close, keyword })
+        })
+        .collect()
+}
+
+// Walks `dir`, harvesting TODO/FIXME/HACK comment markers into the loader's
+// list, each recorded with a `file:line` suffix. Already-imported entries
+// (matched by exact content) are skipped so repeated scans don't create
+// duplicates.
+fn scan(loader: &Loader, dir: &str) -> Result<(), Box<dyn Error>> {
+    let file_contents = fs::read_to_string(loader.path()).unwrap_or_default();
+    let (mut todos, errors) = parse_todos(&file_contents);
+    report_errors(&loader.path(), &errors);
+    let mut next_index = todos.iter().map(|t| t.index).max().unwrap_or(0) + 1;
+    let mut seen: HashSet<String> = todos.iter().map(|t| t.content.clone()).collect();
+
+    let branches = scan_branches();
+    // Never harvest "TODO"s out of the tool's own list files or out of VCS/
+    // build directories; they aren't source code and scanning `.git` is both
+    // wasteful and liable to pick up stale blobs.
+    let own_lists: HashSet<String> = Loader::known_lists()?
+        .into_iter()
+        .map(|(name, _)| format!("{}.txt", name))
+        .collect();
+
+    let mut hits = Vec::new();
+    scan_path(Path::new(dir), &mut hits, &branches, &own_lists)?;
+
+    for hit in hits {
+        if !seen.insert(hit.clone()) {
+            continue;
+        }
+        todos.push(Todo::from_body(next_index, &hit));
+        next_index += 1;
+    }
+
+    write_todos(loader, &mut todos)
+}
+
+fn scan_path(
+    path: &Path,
+    hits: &mut Vec<String>,
+    branches: &[ScanBranch],
+    own_lists: &HashSet<String>
+) -> Result<(), Box<dyn Error>> {
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+
+    if path.is_dir() {
+        if name.starts_with('.') || name == "target" {
+            return Ok(());
+        }
+        for entry in fs::read_dir(path)? {
+            scan_path(&entry?.path(), hits, branches, own_lists)?;
+        }
+        return Ok(());
+    }
+
+    if own_lists.contains(name) {
+        return Ok(());
+    }
+
+    // Skip files that aren't valid UTF-8 source rather than erroring the
+    // whole scan out on a stray binary.
+    if let Ok(contents) = fs::read_to_string(path) {
+        for (i, line) in contents.lines().enumerate() {
+            if let Some(text) = scan_line(branches, line) {
+                hits.push(format!("{} ({}:{})", text, path.display(), i + 1));
+            }
+        }
+    }
+    Ok(())
+}
+
+// Checks a single line against every branch of the combined table, in the
+// form `<open>\s*(TODO|FIXME|HACK)\b(.*?)<close>`, and returns the matched
+// marker text if one of them hits.
+fn scan_line(branches: &[ScanBranch], line: &str) -> Option<String> {
+    for branch in branches {
+        let start = match line.find(branch.open) {
+            Some(start) => start,
+            None => continue
+        };
+        let after_open = &line[start + branch.open.len()..];
+        let body = match branch.close {
+            Some(close) => after_open.find(close).map(|end| &after_open[..end]).unwrap_or(after_open),
+            None => after_open
+        };
+
+        let trimmed = body.trim_start();
+        if let Some(rest) = trimmed.strip_prefix(branch.keyword) {
+            let at_boundary = rest.chars().next().map_or(true, |c| !c.is_alphanumeric());
+            if at_boundary {
+                return Some(format!("{}{}", branch.keyword, rest).trim().to_string());
+            }
+        }
+    }
+    None
+}
+
+// Launches $VISUAL (falling back to $EDITOR, then a platform default) on the
+// loader's list for bulk editing, then re-parses and re-normalizes the file
+// once the editor exits.
+fn edit(loader: &Loader) -> Result<(), Box<dyn Error>> {
+    OpenOptions::new().create(true).append(true).open(loader.path())?;
+
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| default_editor().to_string());
+
+    // $VISUAL/$EDITOR conventionally carries trailing arguments too (e.g.
+    // "vim -c ...", "code --wait"), so only the first word is the program.
+    let mut editor_words = editor.split_whitespace();
+    let editor_program = editor_words.next().unwrap_or(&editor);
+    Command::new(editor_program).args(editor_words).arg(loader.path()).status()?;
+
+    let file_contents = fs::read_to_string(loader.path())?;
+    let (mut todos, errors) = parse_todos(&file_contents);
+    if !errors.is_empty() {
+        report_errors(&loader.path(), &errors);
+        return Err(Box::new(errors.into_iter().next().unwrap()));
+    }
+
+    todos.sort();
+    for (i, todo) in todos.iter_mut().enumerate() {
+        todo.index = i as i32 + 1;
+    }
+
+    write_todos(loader, &mut todos)
+}
+
+#[cfg(target_os = "windows")]
+fn default_editor() -> &'static str {
+    "notepad"
+}
+
+#[cfg(not(target_os = "windows"))]
+fn default_editor() -> &'static str {
+    "vi"
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -148,12 +705,38 @@ mod tests {
         } else {
             panic!("config.noun is None");
         }
+        assert!(config.list.is_none());
+    }
+
+    #[test]
+    fn config_parses_leading_list_flag() {
+        let args: Vec<String> = vec!["todo", "--list", "work", "add", "fix the thing"]
+            .iter()
+            .map(|x| x.to_string())
+            .collect();
+        let config = Config::new(&args[..]).unwrap();
+        assert_eq!(config.list, Some("work".to_string()));
+        assert_eq!(config.verb, "add");
+        assert_eq!(config.noun, Some("fix the thing".to_string()));
+    }
+
+    #[test]
+    fn config_leaves_list_flag_syntax_in_noun_content_alone() {
+        let args: Vec<String> = vec!["todo", "add", "fix", "-l", "flag", "parsing"]
+            .iter()
+            .map(|x| x.to_string())
+            .collect();
+        let config = Config::new(&args[..]).unwrap();
+        assert_eq!(config.list, None);
+        assert_eq!(config.verb, "add");
+        assert_eq!(config.noun, Some("fix -l flag parsing".to_string()));
     }
 
     #[test]
     fn parse() {
         let contents = "2 Something else\n1 Something\n4 Another thing\n";
-        let todos = parse_todos(&contents).unwrap();
+        let (todos, errors) = parse_todos(&contents);
+        assert!(errors.is_empty());
         assert_eq!(todos[0].index, 2);
         assert_eq!(todos[0].content, "Something else");
         assert_eq!(todos[1].index, 1);
@@ -161,4 +744,96 @@ mod tests {
         assert_eq!(todos[2].index, 4);
         assert_eq!(todos[2].content, "Another thing");
     }
+
+    #[test]
+    fn parse_collects_errors_without_aborting() {
+        let contents = "1 Something\nnot-a-number more text\n3\n4 Another thing\n";
+        let (todos, errors) = parse_todos(&contents);
+        assert_eq!(todos.len(), 2);
+        assert_eq!(errors.len(), 2);
+        assert!(matches!(errors[0], TodoError::MalformedLine { line: 2, .. }));
+        assert!(matches!(errors[1], TodoError::MissingContent { line: 3 }));
+    }
+
+    #[test]
+    fn parse_full_todo_txt_format() {
+        let todo = Todo::new(1, "3 (A) 2024-01-05 call plumber +house @phone").unwrap();
+        assert_eq!(todo.index, 3);
+        assert!(!todo.completed);
+        assert_eq!(todo.priority, Some('A'));
+        assert_eq!(todo.creation_date, Some("2024-01-05".to_string()));
+        assert_eq!(todo.completion_date, None);
+        assert_eq!(todo.projects, vec!["house".to_string()]);
+        assert_eq!(todo.contexts, vec!["phone".to_string()]);
+        assert_eq!(todo.content, "call plumber +house @phone");
+    }
+
+    #[test]
+    fn parse_completed_todo_with_both_dates() {
+        let todo = Todo::new(1, "5 x 2024-02-01 2024-01-05 call plumber").unwrap();
+        assert!(todo.completed);
+        assert_eq!(todo.priority, None);
+        assert_eq!(todo.completion_date, Some("2024-02-01".to_string()));
+        assert_eq!(todo.creation_date, Some("2024-01-05".to_string()));
+    }
+
+    #[test]
+    fn round_trips_through_to_line() {
+        let line = "3 (A) 2024-01-05 call plumber +house @phone";
+        let todo = Todo::new(1, line).unwrap();
+        assert_eq!(todo.to_line(), line);
+    }
+
+    #[test]
+    fn completed_todo_never_serializes_a_priority() {
+        let mut todo = Todo::new(1, "5 x 2024-02-01 2024-01-05 call plumber").unwrap();
+        // A priority shouldn't end up on a completed todo, but even if one
+        // does, round-tripping it must not corrupt the dates/content that
+        // follow it in the serialized line.
+        todo.priority = Some('A');
+
+        let round_tripped = Todo::new(1, &todo.to_line()).unwrap();
+        assert_eq!(round_tripped.completion_date, Some("2024-02-01".to_string()));
+        assert_eq!(round_tripped.creation_date, Some("2024-01-05".to_string()));
+        assert_eq!(round_tripped.content, "call plumber");
+    }
+
+    #[test]
+    fn matches_project_and_context_filters() {
+        let todo = Todo::new(1, "1 call plumber +house @phone").unwrap();
+        assert!(todo.matches("+house"));
+        assert!(todo.matches("@phone"));
+        assert!(!todo.matches("+work"));
+    }
+
+    #[test]
+    fn resolve_verb_accepts_unambiguous_abbreviations() {
+        assert_eq!(resolve_verb("sc").unwrap().name, "scan");
+        assert_eq!(resolve_verb("p->").unwrap().name, "depri");
+        // An exact match always wins even if it's also a prefix of another verb.
+        assert_eq!(resolve_verb("list").unwrap().name, "list");
+    }
+
+    #[test]
+    fn resolve_verb_rejects_unknown_commands() {
+        assert!(resolve_verb("bogus").is_err());
+    }
+
+    #[test]
+    fn resolve_verb_reports_ambiguous_abbreviations() {
+        match resolve_verb("li") {
+            Err(e) => assert!(e.to_string().contains("ambiguous")),
+            Ok(_) => panic!("expected an ambiguous command error")
+        }
+    }
+
+    #[test]
+    fn scan_line_finds_keywords_across_comment_styles() {
+        let branches = scan_branches();
+        assert_eq!(scan_line(&branches, "    // TODO: fix parsing"), Some("TODO: fix parsing".to_string()));
+        assert_eq!(scan_line(&branches, "# FIXME handle empty input"), Some("FIXME handle empty input".to_string()));
+        assert_eq!(scan_line(&branches, "x = 1; /* HACK work around driver bug */"), Some("HACK work around driver bug".to_string()));
+        assert_eq!(scan_line(&branches, "// nothing to see here"), None);
+        assert_eq!(scan_line(&branches, "// TODONE: not a real match"), None);
+    }
 }